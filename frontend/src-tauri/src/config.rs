@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+
+/// Backend settings persisted across launches: bind address, selected
+/// transcription model, and compute device.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Config {
+    pub host: String,
+    pub port: u16,
+    pub model: String,
+    pub device: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            host: "127.0.0.1".to_string(),
+            port: 8000,
+            model: "base".to_string(),
+            device: "cpu".to_string(),
+        }
+    }
+}
+
+/// Managed state holding the in-memory config, kept in sync with the file
+/// on disk by `set_config`.
+pub struct ConfigState(pub Mutex<Config>);
+
+fn config_file_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .config_dir()
+        .map_err(|e| format!("Could not resolve OS config directory: {}", e))?;
+    Ok(dir.join("audio-transcriber").join("config.json"))
+}
+
+/// Loads the config from disk, falling back to defaults if it's missing or
+/// unreadable (e.g. first launch).
+pub fn load(app_handle: &tauri::AppHandle) -> Config {
+    config_file_path(app_handle)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save(app_handle: &tauri::AppHandle, config: &Config) -> Result<(), String> {
+    let path = config_file_path(app_handle)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write config file: {}", e))
+}
+
+#[tauri::command]
+pub fn get_config(state: tauri::State<ConfigState>) -> Config {
+    state.0.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn set_config(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<ConfigState>,
+    config: Config,
+) -> Result<(), String> {
+    save(&app_handle, &config)?;
+    *state.0.lock().unwrap() = config;
+
+    // Apply immediately: restart the backend so host/port/model/device
+    // changes take effect now rather than only on the next app launch.
+    crate::restart_backend_for_config_change(&app_handle);
+    Ok(())
+}
@@ -1,30 +1,271 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod config;
+
 use tauri::{Manager, AppHandle};
 use tauri_plugin_shell::{ShellExt, process::CommandEvent};
+use serde::Serialize;
+use std::io::Write;
 use std::sync::Mutex;
 use std::net::TcpStream;
+use std::time::{Duration, Instant};
+use which::which_in;
+
+/// Maximum number of restarts allowed within `RESTART_WINDOW` before the
+/// supervisor gives up to avoid crash-looping the sidecar.
+const MAX_RESTARTS_PER_WINDOW: usize = 5;
+const RESTART_WINDOW: Duration = Duration::from_secs(60);
+
+/// How long to wait for the backend to exit on its own after a graceful
+/// shutdown request before falling back to `child.kill()`.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Executable tried when the bundled sidecar isn't present and the backend
+/// has to be resolved from the user's `PATH` instead. We only look for the
+/// backend binary itself here: a bare `python3` with no script/module to run
+/// would just open an interactive interpreter, not start the server, so an
+/// interpreter fallback is intentionally out of scope until there's a real
+/// entry point to hand it.
+const BACKEND_PATH_CANDIDATES: [&str; 1] = ["main_with_assets"];
+
+/// Managed state for the backend sidecar: the running child (if any), plus
+/// restart bookkeeping for the crash-loop guard. `child` is only ever
+/// `Some` for a process this instance spawned itself — a listener found
+/// already bound on startup (e.g. another instance's backend) is never
+/// stored here, so shutdown/restart logic never touches a process we don't
+/// own.
+struct BackendProcess {
+    child: Mutex<Option<tauri_plugin_shell::process::CommandChild>>,
+    restart_history: Mutex<Vec<Instant>>,
+    /// Set before an intentional shutdown is requested, so the `Terminated`
+    /// handler knows the exit wasn't a crash and skips the restart policy.
+    shutting_down: Mutex<bool>,
+}
+
+impl Default for BackendProcess {
+    fn default() -> Self {
+        BackendProcess {
+            child: Mutex::new(None),
+            restart_history: Mutex::new(Vec::new()),
+            shutting_down: Mutex::new(false),
+        }
+    }
+}
+
+/// Returns the configured `host:port` the backend should bind/connect to.
+fn backend_address(config: &config::Config) -> String {
+    format!("{}:{}", config.host, config.port)
+}
+
+/// Locates the backend executable on `PATH` using the `which` crate, for use
+/// when the bundled sidecar isn't available (e.g. a dev build run outside
+/// the packaged app).
+fn resolve_backend_program(path_env: &str) -> Result<String, String> {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    for candidate in BACKEND_PATH_CANDIDATES {
+        if let Ok(found) = which_in(candidate, Some(path_env), &cwd) {
+            return Ok(found.to_string_lossy().to_string());
+        }
+    }
+    Err(format!(
+        "Could not locate any of {:?} on PATH",
+        BACKEND_PATH_CANDIDATES
+    ))
+}
+
+/// Payload for the `backend://log` event: one line of sidecar output.
+#[derive(Clone, Serialize)]
+struct BackendLogPayload {
+    level: String,
+    line: String,
+}
+
+/// Payload for the `backend://status` event: a lifecycle transition.
+#[derive(Clone, Serialize)]
+struct BackendStatusPayload {
+    status: String,
+}
+
+/// Payload for the `app://second-instance` event: the argv/cwd a duplicate
+/// launch was started with, forwarded to the already-running instance.
+#[derive(Clone, Serialize)]
+struct SecondInstancePayload {
+    argv: Vec<String>,
+    cwd: String,
+}
+
+fn emit_log(app_handle: &AppHandle, level: &str, line: &str) {
+    let _ = app_handle.emit(
+        "backend://log",
+        BackendLogPayload { level: level.to_string(), line: line.to_string() },
+    );
+}
+
+fn emit_status(app_handle: &AppHandle, status: &str) {
+    let _ = app_handle.emit(
+        "backend://status",
+        BackendStatusPayload { status: status.to_string() },
+    );
+}
+
+fn is_backend_listening(addr: &str) -> bool {
+    TcpStream::connect(addr).is_ok()
+}
+
+/// Cap on the delay between readiness poll attempts in `poll_until_ready`.
+const READY_POLL_MAX_DELAY_MS: u64 = 10_000;
+
+/// Doubles `delay_ms` for the next readiness poll attempt, capped at
+/// `READY_POLL_MAX_DELAY_MS`.
+fn next_backoff_delay(delay_ms: u64) -> u64 {
+    (delay_ms * 2).min(READY_POLL_MAX_DELAY_MS)
+}
+
+/// Polls `is_backend_listening` with capped exponential backoff (100ms, 200ms,
+/// 400ms, ... up to 10s between attempts) until it succeeds or `timeout_ms`
+/// elapses.
+async fn poll_until_ready(addr: &str, timeout_ms: u64) -> Result<(), String> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+    let mut delay_ms: u64 = 100;
+
+    loop {
+        if is_backend_listening(addr) {
+            return Ok(());
+        }
+        if std::time::Instant::now() >= deadline {
+            return Err(format!("Timed out after {}ms waiting for backend to become ready", timeout_ms));
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+        delay_ms = next_backoff_delay(delay_ms);
+    }
+}
+
+/// Awaitable by the frontend before it issues its first transcription
+/// request, so it doesn't race the backend's startup.
+#[tauri::command]
+async fn wait_for_backend(app_handle: AppHandle, timeout_ms: u64) -> Result<(), String> {
+    let config_state: tauri::State<config::ConfigState> = app_handle.state();
+    let addr = backend_address(&config_state.0.lock().unwrap());
+    poll_until_ready(&addr, timeout_ms).await
+}
+
+/// Prunes `history` to entries within `RESTART_WINDOW` of `now`, then
+/// records `now` as a new restart attempt unless `MAX_RESTARTS_PER_WINDOW`
+/// has already been reached. Returns the new restart count, or `None` if
+/// the crash-loop guard should kick in instead.
+fn record_restart_attempt(history: &mut Vec<Instant>, now: Instant) -> Option<usize> {
+    history.retain(|t| now.duration_since(*t) < RESTART_WINDOW);
+
+    if history.len() >= MAX_RESTARTS_PER_WINDOW {
+        None
+    } else {
+        history.push(now);
+        Some(history.len())
+    }
+}
+
+/// Applies the restart policy after an unexpected sidecar exit: re-spawns
+/// the backend with a short backoff, unless `MAX_RESTARTS_PER_WINDOW` has
+/// already been hit within `RESTART_WINDOW`, in which case it gives up.
+fn attempt_restart(app_handle: &AppHandle) {
+    let backend_state: tauri::State<BackendProcess> = app_handle.state();
+    let restart_count = {
+        let mut history = backend_state.restart_history.lock().unwrap();
+        record_restart_attempt(&mut history, Instant::now())
+    };
+
+    let restart_count = match restart_count {
+        Some(count) => count,
+        None => {
+            eprintln!(
+                "Backend crashed again after {} restarts within {:?}; giving up",
+                MAX_RESTARTS_PER_WINDOW, RESTART_WINDOW
+            );
+            emit_status(app_handle, "gave-up");
+            let _ = app_handle.emit("backend://gave-up", ());
+            return;
+        }
+    };
+
+    println!("Backend crashed unexpectedly; restarting (attempt {})", restart_count);
+    emit_status(app_handle, "restarting");
+    let _ = app_handle.emit("backend://restarting", restart_count);
+
+    let restart_app_handle = app_handle.clone();
+    let backoff = Duration::from_millis(500 * restart_count as u64);
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(backoff).await;
+        if let Err(e) = start_backend(restart_app_handle) {
+            eprintln!("Failed to restart backend: {}", e);
+        }
+    });
+}
+
+/// Best-effort notification asking the backend to shut itself down. The
+/// backend isn't required to honor it; `graceful_shutdown` falls back to
+/// `child.kill()` if it doesn't.
+fn send_shutdown_request(addr: &str) {
+    if let Ok(mut stream) = TcpStream::connect(addr) {
+        let request = format!(
+            "POST /shutdown HTTP/1.1\r\nHost: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            addr
+        );
+        let _ = stream.write_all(request.as_bytes());
+    }
+}
 
-struct BackendProcess(Mutex<Option<tauri_plugin_shell::process::CommandChild>>);
+/// Asks the backend to shut down, waits up to `SHUTDOWN_TIMEOUT` for it to
+/// stop listening on its own, and only SIGKILLs it via `child.kill()` if it
+/// hasn't exited in time. Used on both window close and app exit so shutdown
+/// behaves the same either way. Waits asynchronously so it never blocks the
+/// main/event-dispatch thread.
+async fn graceful_shutdown(app_handle: &AppHandle) {
+    let backend_state: tauri::State<BackendProcess> = app_handle.state();
+    if backend_state.child.lock().unwrap().is_none() {
+        return;
+    }
+
+    // Mark this exit as requested before signaling the backend, so the
+    // Terminated handler doesn't race us into restarting it.
+    *backend_state.shutting_down.lock().unwrap() = true;
+
+    let config_state: tauri::State<config::ConfigState> = app_handle.state();
+    let addr = backend_address(&config_state.0.lock().unwrap());
 
-fn is_backend_listening() -> bool {
-    TcpStream::connect("127.0.0.1:8000").is_ok()
+    println!("Requesting graceful backend shutdown at {}", addr);
+    send_shutdown_request(&addr);
+
+    let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+    while Instant::now() < deadline && is_backend_listening(&addr) {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    if let Some(child) = backend_state.child.lock().unwrap().take() {
+        println!("Backend did not shut down gracefully in time; killing it");
+        let _ = child.kill();
+    }
 }
 
 #[tauri::command]
 fn start_backend(app_handle: AppHandle) -> Result<String, String> {
     println!("Attempting to start backend sidecar...");
 
-    // If port 8000 already has a listener, don't spawn another backend
-    if is_backend_listening() {
-        println!("Backend already listening on 127.0.0.1:8000; skipping spawn.");
+    let backend_state: tauri::State<BackendProcess> = app_handle.state();
+    let config_state: tauri::State<config::ConfigState> = app_handle.state();
+    let config = config_state.0.lock().unwrap().clone();
+    let addr = backend_address(&config);
+
+    // If the configured address already has a listener, don't spawn another
+    // backend. We deliberately do NOT store a child for it below: since we
+    // didn't start it, we must never kill or restart it.
+    if is_backend_listening(&addr) {
+        println!("Backend already listening on {} (externally owned); skipping spawn.", addr);
         return Ok("Backend already running".to_string());
     }
 
     // Also avoid double-spawn if we already have a child stored
-    let backend_state: tauri::State<BackendProcess> = app_handle.state();
-    if backend_state.0.lock().unwrap().is_some() {
+    if backend_state.child.lock().unwrap().is_some() {
         println!("Backend process already spawned; skipping spawn.");
         return Ok("Backend already spawned".to_string());
     }
@@ -39,35 +280,76 @@ fn start_backend(app_handle: AppHandle) -> Result<String, String> {
         }
     }
     println!("Starting backend with PATH={}", path);
+    emit_status(&app_handle, "spawning");
 
-    let sidecar_command = app_handle.shell().sidecar("main_with_assets")
-        .map_err(|e| format!("Failed to create sidecar command: {}", e))?
-        .env("PATH", path);
+    let sidecar_command = match app_handle.shell().sidecar("main_with_assets") {
+        Ok(cmd) => cmd.env("PATH", path.clone()),
+        Err(e) => {
+            println!("Bundled sidecar unavailable ({}); resolving backend from PATH instead", e);
+            let program = resolve_backend_program(&path)?;
+            println!("Resolved backend executable on PATH: {}", program);
+            app_handle.shell().command(program).env("PATH", path.clone())
+        }
+    };
+    let sidecar_command = sidecar_command
+        .env("TRANSCRIBER_HOST", config.host.clone())
+        .env("TRANSCRIBER_PORT", config.port.to_string())
+        .env("TRANSCRIBER_MODEL", config.model.clone())
+        .env("TRANSCRIBER_DEVICE", config.device.clone());
 
     let (mut rx, child) = sidecar_command
         .spawn()
-        .map_err(|e| format!("Failed to spawn backend sidecar: {}", e))?;
+        .map_err(|e| {
+            emit_status(&app_handle, "error");
+            format!("Failed to spawn backend sidecar: {}", e)
+        })?;
 
-    // Store the process handle
-    *backend_state.0.lock().unwrap() = Some(child);
+    // Store the process handle and clear any stale shutdown flag from a
+    // previous run, so a future unexpected exit of *this* process restarts.
+    let spawned_pid = child.pid();
+    *backend_state.child.lock().unwrap() = Some(child);
+    *backend_state.shutting_down.lock().unwrap() = false;
 
     // Handle stdout/stderr in background
+    let events_app_handle = app_handle.clone();
     tauri::async_runtime::spawn(async move {
         while let Some(event) = rx.recv().await {
             match event {
                 CommandEvent::Stdout(line_bytes) => {
                     let line = String::from_utf8_lossy(&line_bytes);
                     println!("Backend stdout: {}", line);
+                    emit_log(&events_app_handle, "stdout", &line);
                 }
                 CommandEvent::Stderr(line_bytes) => {
                     let line = String::from_utf8_lossy(&line_bytes);
                     eprintln!("Backend stderr: {}", line);
+                    emit_log(&events_app_handle, "stderr", &line);
                 }
                 CommandEvent::Error(error) => {
                     eprintln!("Backend error: {}", error);
+                    emit_log(&events_app_handle, "error", &error);
+                    emit_status(&events_app_handle, "error");
                 }
                 CommandEvent::Terminated(payload) => {
                     println!("Backend terminated with code: {:?}", payload.code);
+                    let events_state: tauri::State<BackendProcess> = events_app_handle.state();
+                    let mut stored = events_state.child.lock().unwrap();
+                    // A newer backend has already replaced this one (e.g. the
+                    // restart after a config change): leave its state alone.
+                    if stored.as_ref().map_or(false, |c| c.pid() != spawned_pid) {
+                        break;
+                    }
+                    *stored = None;
+                    drop(stored);
+                    emit_status(&events_app_handle, "terminated");
+
+                    // A shutdown already in progress means this exit was
+                    // requested, not a crash - never restart for it, or
+                    // we'd spawn a brand-new backend while the app quits.
+                    let was_requested = *events_state.shutting_down.lock().unwrap();
+                    if !was_requested && payload.code != Some(0) {
+                        attempt_restart(&events_app_handle);
+                    }
                     break;
                 }
                 _ => {
@@ -78,14 +360,66 @@ fn start_backend(app_handle: AppHandle) -> Result<String, String> {
         }
     });
 
+    // Wait for the backend to actually accept connections before reporting
+    // it ready; spawning the process is not the same as it being up.
+    let ready_app_handle = app_handle.clone();
+    let ready_addr = addr.clone();
+    tauri::async_runtime::spawn(async move {
+        match poll_until_ready(&ready_addr, 10_000).await {
+            Ok(()) => {
+                let _ = ready_app_handle.emit("backend://ready", ());
+                emit_status(&ready_app_handle, "ready");
+            }
+            Err(e) => {
+                emit_log(&ready_app_handle, "error", &e);
+                emit_status(&ready_app_handle, "error");
+            }
+        }
+    });
+
     Ok("Backend started successfully".to_string())
 }
 
+/// Restarts the backend sidecar so it picks up a just-saved config change
+/// (host/port/model/device) immediately, instead of only on next launch.
+/// No-op if no backend is currently running (e.g. it failed to start) -
+/// `start_backend` will read the new config whenever it's next spawned.
+pub(crate) fn restart_backend_for_config_change(app_handle: &AppHandle) {
+    let backend_state: tauri::State<BackendProcess> = app_handle.state();
+    if backend_state.child.lock().unwrap().is_none() {
+        return;
+    }
+
+    let restart_app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        graceful_shutdown(&restart_app_handle).await;
+        if let Err(e) = start_backend(restart_app_handle.clone()) {
+            eprintln!("Failed to restart backend after config change: {}", e);
+        }
+    });
+}
+
 fn main() {
     tauri::Builder::default()
+        // Must be registered first: a second launch is intercepted here and
+        // never reaches `setup`, so it can't race the first instance to
+        // spawn a backend or bind the port.
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            println!("Second instance launched with argv={:?} cwd={}", argv, cwd);
+            let _ = app.emit("app://second-instance", SecondInstancePayload { argv, cwd });
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.set_focus();
+            }
+        }))
         .plugin(tauri_plugin_shell::init())
-        .manage(BackendProcess(Default::default()))
+        .manage(BackendProcess::default())
         .setup(|app| {
+            // Load persisted backend settings before the backend is spawned
+            let app_handle = app.handle().clone();
+            let loaded_config = config::load(&app_handle);
+            app.manage(config::ConfigState(Mutex::new(loaded_config)));
+
             // Auto-start backend on app launch
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -97,18 +431,114 @@ fn main() {
         })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::Destroyed = event {
-                // Clean up backend process when app closes
-                let app_handle = window.app_handle();
-                let backend_state: tauri::State<BackendProcess> = app_handle.state();
-                let mutex = &backend_state.0;
-                let mut guard = mutex.lock().unwrap();
-                if let Some(child) = guard.take() {
-                    let _ = child.kill();
-                }
+                // Shut the backend down gracefully when the window closes,
+                // off the event-dispatch thread so closing never stalls
+                let app_handle = window.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    graceful_shutdown(&app_handle).await;
+                });
             }
         })
-        .invoke_handler(tauri::generate_handler![start_backend])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler(tauri::generate_handler![
+            start_backend,
+            wait_for_backend,
+            config::get_config,
+            config::set_config
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::ExitRequested { api, code: None, .. } = event {
+                // Same graceful-shutdown path as a window close, so behavior
+                // is consistent whether the user closes the window or quits.
+                // Defer the actual exit until shutdown finishes so it still
+                // happens, just without blocking this thread while waiting.
+                // The `exit(0)` below arrives with `code: Some(0)` and is let
+                // through.
+                api.prevent_exit();
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    graceful_shutdown(&app_handle).await;
+                    app_handle.exit(0);
+                });
+            }
+        });
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("audio-transcriber-test-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn make_executable(dir: &std::path::Path, name: &str) {
+        let path = dir.join(name);
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        let mut perms = fs::metadata(&path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&path, perms).unwrap();
+    }
+
+    #[test]
+    fn resolve_backend_program_finds_executable_on_path() {
+        let dir = scratch_dir("found");
+        make_executable(&dir, "main_with_assets");
+
+        let resolved = resolve_backend_program(dir.to_str().unwrap()).unwrap();
+        assert_eq!(std::path::Path::new(&resolved), dir.join("main_with_assets"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 
-}
\ No newline at end of file
+    #[test]
+    fn resolve_backend_program_errs_when_missing_from_path() {
+        let dir = scratch_dir("missing");
+
+        let result = resolve_backend_program(dir.to_str().unwrap());
+        assert!(result.is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn next_backoff_delay_doubles_up_to_the_cap() {
+        let mut delay = 100;
+        let mut seen = vec![delay];
+        for _ in 0..8 {
+            delay = next_backoff_delay(delay);
+            seen.push(delay);
+        }
+        assert_eq!(seen, vec![100, 200, 400, 800, 1600, 3200, 6400, 10_000, 10_000]);
+    }
+
+    #[test]
+    fn record_restart_attempt_gives_up_after_the_cap() {
+        let mut history = Vec::new();
+        let now = Instant::now();
+
+        for expected in 1..=MAX_RESTARTS_PER_WINDOW {
+            assert_eq!(record_restart_attempt(&mut history, now), Some(expected));
+        }
+        assert_eq!(record_restart_attempt(&mut history, now), None);
+        assert_eq!(history.len(), MAX_RESTARTS_PER_WINDOW);
+    }
+
+    #[test]
+    fn record_restart_attempt_prunes_entries_outside_the_window() {
+        let now = Instant::now();
+        let stale = now - RESTART_WINDOW - Duration::from_secs(1);
+        let mut history = vec![stale; MAX_RESTARTS_PER_WINDOW];
+
+        // All prior attempts are outside RESTART_WINDOW, so this one should
+        // be allowed even though `history` is already at the cap in length.
+        assert_eq!(record_restart_attempt(&mut history, now), Some(1));
+        assert_eq!(history.len(), 1);
+    }
+}